@@ -0,0 +1,386 @@
+//! A lazy, offset-tracking view over raw JSON bytes.
+//!
+//! Unlike [`crate::from_json_str`], which eagerly builds the whole
+//! `HashMap`/`Vec` tree, a [`JsonCursor`] only remembers the byte span of the
+//! node it points at. Navigating with [`JsonCursor::get`] or
+//! [`JsonCursor::index`] scans just far enough to locate the child's span;
+//! the child's contents are only materialized when [`JsonCursor::as_value`]
+//! is called. This lets callers pull one field out of a multi-megabyte
+//! object without paying to build the rest of it.
+
+use crate::parser::{parse_value_at, Value, ValueKind};
+use crate::ParseError;
+
+const JSON_WHITESPACE_CHARS: &[u8] = b" \n\r\t";
+
+#[derive(Debug)]
+pub struct JsonCursor<'a> {
+    bytes: &'a [u8],
+    start: usize,
+}
+
+impl<'a> JsonCursor<'a> {
+    /// Creates a cursor over the first JSON value in `src`.
+    pub fn new(src: &'a str) -> Result<Self, ParseError> {
+        let bytes = src.as_bytes();
+        let start = skip_whitespace(bytes, 0);
+        skip_value(bytes, start).map_err(|e| e.with_source(bytes))?;
+        Ok(Self { bytes, start })
+    }
+
+    /// Looks up `key` in the current node, which must be a JSON object.
+    /// Returns `Ok(None)` if the node isn't an object or has no such key.
+    pub fn get(&self, key: &str) -> Result<Option<JsonCursor<'a>>, ParseError> {
+        self.get_inner(key).map_err(|e| e.with_source(self.bytes))
+    }
+
+    fn get_inner(&self, key: &str) -> Result<Option<JsonCursor<'a>>, ParseError> {
+        let mut pos = self.start;
+        if self.bytes.get(pos) != Some(&b'{') {
+            return Ok(None);
+        }
+        pos += 1;
+
+        loop {
+            pos = skip_whitespace(self.bytes, pos);
+            if self.bytes.get(pos) == Some(&b'}') {
+                return Ok(None);
+            }
+
+            let (k, after_key) = parse_key(self.bytes, pos)?;
+            pos = skip_whitespace(self.bytes, after_key);
+            pos = expect(self.bytes, pos, b':')?;
+            pos = skip_whitespace(self.bytes, pos);
+
+            let value_start = pos;
+            let value_end = skip_value(self.bytes, pos)?;
+
+            if k == key {
+                return Ok(Some(JsonCursor {
+                    bytes: self.bytes,
+                    start: value_start,
+                }));
+            }
+
+            pos = skip_whitespace(self.bytes, value_end);
+            match self.bytes.get(pos) {
+                Some(b',') => pos += 1,
+                Some(b'}') => return Ok(None),
+                _ => return Err(ParseError::syntax(pos)),
+            }
+        }
+    }
+
+    /// Returns the element at `index` in the current node, which must be a
+    /// JSON array.
+    pub fn index(&self, index: usize) -> Result<Option<JsonCursor<'a>>, ParseError> {
+        self.index_inner(index).map_err(|e| e.with_source(self.bytes))
+    }
+
+    fn index_inner(&self, index: usize) -> Result<Option<JsonCursor<'a>>, ParseError> {
+        let mut pos = self.start;
+        if self.bytes.get(pos) != Some(&b'[') {
+            return Ok(None);
+        }
+        pos += 1;
+
+        let mut i = 0;
+        loop {
+            pos = skip_whitespace(self.bytes, pos);
+            if self.bytes.get(pos) == Some(&b']') {
+                return Ok(None);
+            }
+
+            let elem_start = pos;
+            let elem_end = skip_value(self.bytes, pos)?;
+
+            if i == index {
+                return Ok(Some(JsonCursor {
+                    bytes: self.bytes,
+                    start: elem_start,
+                }));
+            }
+
+            pos = skip_whitespace(self.bytes, elem_end);
+            match self.bytes.get(pos) {
+                Some(b',') => pos += 1,
+                Some(b']') => return Ok(None),
+                _ => return Err(ParseError::syntax(pos)),
+            }
+            i += 1;
+        }
+    }
+
+    /// Lists the keys of the current node without parsing any of its values.
+    /// Returns an empty vector if the current node isn't an object.
+    pub fn keys(&self) -> Result<Vec<String>, ParseError> {
+        self.keys_inner().map_err(|e| e.with_source(self.bytes))
+    }
+
+    fn keys_inner(&self) -> Result<Vec<String>, ParseError> {
+        let mut keys = vec![];
+        let mut pos = self.start;
+        if self.bytes.get(pos) != Some(&b'{') {
+            return Ok(keys);
+        }
+        pos += 1;
+
+        loop {
+            pos = skip_whitespace(self.bytes, pos);
+            if self.bytes.get(pos) == Some(&b'}') {
+                return Ok(keys);
+            }
+
+            let (k, after_key) = parse_key(self.bytes, pos)?;
+            keys.push(k);
+            pos = skip_whitespace(self.bytes, after_key);
+            pos = expect(self.bytes, pos, b':')?;
+            pos = skip_whitespace(self.bytes, pos);
+            pos = skip_value(self.bytes, pos)?;
+
+            pos = skip_whitespace(self.bytes, pos);
+            match self.bytes.get(pos) {
+                Some(b',') => pos += 1,
+                Some(b'}') => return Ok(keys),
+                _ => return Err(ParseError::syntax(pos)),
+            }
+        }
+    }
+
+    /// Counts the elements of an array or the keys of an object, without
+    /// fully parsing any of the values.
+    pub fn len(&self) -> Result<usize, ParseError> {
+        self.len_inner().map_err(|e| e.with_source(self.bytes))
+    }
+
+    fn len_inner(&self) -> Result<usize, ParseError> {
+        match self.bytes.get(self.start) {
+            Some(b'{') => Ok(self.keys_inner()?.len()),
+            Some(b'[') => {
+                let mut count = 0;
+                let mut pos = self.start + 1;
+                loop {
+                    pos = skip_whitespace(self.bytes, pos);
+                    if self.bytes.get(pos) == Some(&b']') {
+                        return Ok(count);
+                    }
+
+                    pos = skip_value(self.bytes, pos)?;
+                    count += 1;
+
+                    pos = skip_whitespace(self.bytes, pos);
+                    match self.bytes.get(pos) {
+                        Some(b',') => pos += 1,
+                        Some(b']') => return Ok(count),
+                        _ => return Err(ParseError::syntax(pos)),
+                    }
+                }
+            }
+            _ => Ok(0),
+        }
+    }
+
+    /// Returns `true` if the current node is an empty array/object, or isn't
+    /// an array/object at all.
+    pub fn is_empty(&self) -> Result<bool, ParseError> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Fully parses the current node into an owned [`Value`].
+    pub fn as_value(&self) -> Result<Value, ParseError> {
+        self.as_value_inner().map_err(|e| e.with_source(self.bytes))
+    }
+
+    fn as_value_inner(&self) -> Result<Value, ParseError> {
+        let expected_end = skip_value(self.bytes, self.start)?;
+        let (value, end) = parse_value_at(self.bytes, self.start)?;
+        if end != expected_end {
+            return Err(ParseError::syntax(self.start));
+        }
+        Ok(value)
+    }
+}
+
+fn parse_key(bytes: &[u8], pos: usize) -> Result<(String, usize), ParseError> {
+    let (value, end) = parse_value_at(bytes, pos)?;
+    match value.into_kind() {
+        ValueKind::String(s) => Ok((s, end)),
+        _ => Err(ParseError::syntax(pos)),
+    }
+}
+
+fn expect(bytes: &[u8], pos: usize, b: u8) -> Result<usize, ParseError> {
+    if bytes.get(pos) == Some(&b) {
+        Ok(pos + 1)
+    } else {
+        Err(ParseError::syntax(pos))
+    }
+}
+
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while matches!(bytes.get(pos), Some(b) if JSON_WHITESPACE_CHARS.contains(b)) {
+        pos += 1;
+    }
+    pos
+}
+
+/// Scans (without materializing) the JSON value starting at `pos`, returning
+/// the byte offset just past it.
+fn skip_value(bytes: &[u8], pos: usize) -> Result<usize, ParseError> {
+    match bytes.get(pos) {
+        Some(b'"') => skip_string(bytes, pos),
+        Some(b'{') => skip_bracketed(bytes, pos, b'{', b'}'),
+        Some(b'[') => skip_bracketed(bytes, pos, b'[', b']'),
+        Some(b't') => skip_literal(bytes, pos, b"true"),
+        Some(b'f') => skip_literal(bytes, pos, b"false"),
+        Some(b'n') => skip_literal(bytes, pos, b"null"),
+        Some(b'-') | Some(b'0'..=b'9') => Ok(skip_number(bytes, pos)),
+        _ => Err(ParseError::syntax(pos)),
+    }
+}
+
+fn skip_string(bytes: &[u8], pos: usize) -> Result<usize, ParseError> {
+    let mut i = pos + 1;
+    while let Some(&b) = bytes.get(i) {
+        match b {
+            b'"' => return Ok(i + 1),
+            b'\\' => i += 2,
+            _ => i += 1,
+        }
+    }
+    Err(ParseError::syntax(i))
+}
+
+fn skip_bracketed(bytes: &[u8], pos: usize, open: u8, close: u8) -> Result<usize, ParseError> {
+    let mut i = pos + 1;
+    let mut depth = 1usize;
+
+    while depth > 0 {
+        match bytes.get(i) {
+            Some(b'"') => i = skip_string(bytes, i)?,
+            Some(&b) if b == open => {
+                depth += 1;
+                i += 1;
+            }
+            Some(&b) if b == close => {
+                depth -= 1;
+                i += 1;
+            }
+            Some(_) => i += 1,
+            None => return Err(ParseError::syntax(i)),
+        }
+    }
+
+    Ok(i)
+}
+
+fn skip_literal(bytes: &[u8], pos: usize, literal: &[u8]) -> Result<usize, ParseError> {
+    if bytes.get(pos..pos + literal.len()) == Some(literal) {
+        Ok(pos + literal.len())
+    } else {
+        Err(ParseError::syntax(pos))
+    }
+}
+
+fn skip_number(bytes: &[u8], pos: usize) -> usize {
+    let mut i = pos;
+    // Mirrors `parser::JSON_NUMBER_CHARS` so a cursor's notion of a number's
+    // span always agrees with what `Parser::parse_number` would accept.
+    while matches!(bytes.get(i), Some(b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')) {
+        i += 1;
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_and_as_value() {
+        let src = r#"{"a":1,"b":{"c":2,"d":[3,4,5]}}"#;
+        let cursor = JsonCursor::new(src).unwrap();
+
+        let b = cursor.get("b").unwrap().unwrap();
+        let c = b.get("c").unwrap().unwrap();
+        assert!(matches!(c.as_value().unwrap().into_kind(), ValueKind::Number(_)));
+
+        assert!(cursor.get("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_index() {
+        let src = r#"{"b":{"d":[3,4,5]}}"#;
+        let cursor = JsonCursor::new(src).unwrap();
+        let d = cursor.get("b").unwrap().unwrap().get("d").unwrap().unwrap();
+
+        assert_eq!(d.len().unwrap(), 3);
+        let elem = d.index(1).unwrap().unwrap();
+        let ValueKind::Number(n) = elem.as_value().unwrap().into_kind() else {
+            panic!("expected a number");
+        };
+        assert_eq!(n.to_string(), "4");
+        assert!(d.index(10).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_keys() {
+        let src = r#"{"a":1,"b":2,"c":{"nested":true}}"#;
+        let cursor = JsonCursor::new(src).unwrap();
+        assert_eq!(cursor.keys().unwrap(), vec!["a", "b", "c"]);
+        assert_eq!(cursor.len().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_exponent_numbers_are_not_truncated() {
+        let src = r#"{"a": 1E5, "b": 2.5e+2}"#;
+        let cursor = JsonCursor::new(src).unwrap();
+
+        let a = cursor.get("a").unwrap().unwrap();
+        let ValueKind::Number(n) = a.as_value().unwrap().into_kind() else {
+            panic!("expected a number");
+        };
+        assert_eq!(n.to_string(), "100000");
+
+        let b = cursor.get("b").unwrap().unwrap();
+        let ValueKind::Number(n) = b.as_value().unwrap().into_kind() else {
+            panic!("expected a number");
+        };
+        assert_eq!(n.to_string(), "250");
+    }
+
+    #[test]
+    fn test_len_and_is_empty() {
+        let src = r#"{"arr": [1, 2, 3], "empty": []}"#;
+        let cursor = JsonCursor::new(src).unwrap();
+
+        let arr = cursor.get("arr").unwrap().unwrap();
+        assert_eq!(arr.len().unwrap(), 3);
+        assert!(!arr.is_empty().unwrap());
+
+        let empty = cursor.get("empty").unwrap().unwrap();
+        assert_eq!(empty.len().unwrap(), 0);
+        assert!(empty.is_empty().unwrap());
+    }
+
+    #[test]
+    fn test_errors_carry_line_column_diagnostics() {
+        let cursor = JsonCursor::new(r#"{"a": 1, "b": }"#).unwrap();
+        let err = cursor.get("b").unwrap_err();
+
+        let rendered = err.to_string();
+        assert!(rendered.contains("line 1"));
+        assert!(rendered.contains('^'));
+    }
+
+    #[test]
+    fn test_skips_escaped_quotes_in_strings() {
+        let src = r#"{"a":"x\"y","b":2}"#;
+        let cursor = JsonCursor::new(src).unwrap();
+        let b = cursor.get("b").unwrap().unwrap();
+        let ValueKind::Number(n) = b.as_value().unwrap().into_kind() else {
+            panic!("expected a number");
+        };
+        assert_eq!(n.to_string(), "2");
+    }
+}
@@ -0,0 +1,151 @@
+use crate::parser::{Number, Object, Value, ValueKind};
+use crate::Json;
+
+pub fn to_json_str(json: &Json) -> String {
+    let mut out = String::new();
+    for (i, v) in json.values().iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        encode_value(v, None, 0, &mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+pub(crate) fn to_json_str_value(value: &Value) -> String {
+    let mut out = String::new();
+    encode_value(value, None, 0, &mut out);
+    out
+}
+
+/// Quotes and escapes `s` as a JSON string literal, reusing the encoder's
+/// escape table so it stays in sync with [`to_json_str`].
+pub(crate) fn escape_json_string(s: &str) -> String {
+    let mut out = String::new();
+    encode_string(s, &mut out);
+    out
+}
+
+pub fn to_json_str_pretty(json: &Json, indent: usize) -> String {
+    let mut out = String::new();
+    let mut first = true;
+    for v in json.values() {
+        if !first {
+            out.push('\n');
+        }
+        first = false;
+        encode_value(v, Some(indent), 0, &mut out);
+    }
+    out
+}
+
+fn encode_value(value: &Value, pretty: Option<usize>, level: usize, out: &mut String) {
+    match value.kind() {
+        ValueKind::Null => out.push_str("null"),
+        ValueKind::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        ValueKind::Number(n) => encode_number(n, out),
+        ValueKind::String(s) => encode_string(s, out),
+        ValueKind::Array(a) => encode_array(a, pretty, level, out),
+        ValueKind::Object(o) => encode_object(o, pretty, level, out),
+    }
+}
+
+fn encode_number(n: &Number, out: &mut String) {
+    out.push_str(&n.to_string());
+}
+
+fn encode_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0C}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn encode_array(a: &[Value], pretty: Option<usize>, level: usize, out: &mut String) {
+    if a.is_empty() {
+        out.push_str("[]");
+        return;
+    }
+
+    out.push('[');
+    for (i, v) in a.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        newline_indent(pretty, level + 1, out);
+        encode_value(v, pretty, level + 1, out);
+    }
+    newline_indent(pretty, level, out);
+    out.push(']');
+}
+
+fn encode_object(o: &Object, pretty: Option<usize>, level: usize, out: &mut String) {
+    if o.is_empty() {
+        out.push_str("{}");
+        return;
+    }
+
+    out.push('{');
+    for (i, (k, v)) in o.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        newline_indent(pretty, level + 1, out);
+        encode_string(k, out);
+        out.push(':');
+        if pretty.is_some() {
+            out.push(' ');
+        }
+        encode_value(v, pretty, level + 1, out);
+    }
+    newline_indent(pretty, level, out);
+    out.push('}');
+}
+
+fn newline_indent(pretty: Option<usize>, level: usize, out: &mut String) {
+    if let Some(indent) = pretty {
+        out.push('\n');
+        for _ in 0..level * indent {
+            out.push(' ');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compact() {
+        let json = crate::from_json_str(r#"{"a":1,"b":[true,null,"x"]}"#).unwrap();
+        let s = to_json_str(&json);
+        let back = crate::from_json_str(&s);
+        assert!(back.is_ok());
+    }
+
+    #[test]
+    fn test_pretty() {
+        let json = crate::from_json_str(r#"{"a":1}"#).unwrap();
+        let s = to_json_str_pretty(&json, 2);
+        assert!(s.contains('\n'));
+    }
+
+    #[test]
+    fn test_escape() {
+        let json = crate::from_json_str("[\"line\tbreak\"]").unwrap();
+        let s = to_json_str(&json);
+        assert!(s.contains("\\t"));
+    }
+}
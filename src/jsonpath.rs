@@ -0,0 +1,785 @@
+//! A small JSONPath query engine over a parsed [`Json`] tree.
+//!
+//! The pipeline mirrors a classic jsonpath implementation: a [`tokenize`]
+//! step turns the path string into [`Token`]s, [`parse_path`] turns those
+//! into a [`Segment`] AST, and [`select`] walks the AST against the value
+//! tree, collecting borrowed references to the matching nodes.
+
+use crate::parser::{Number, Value, ValueKind};
+use crate::Json;
+use std::{error::Error, fmt};
+
+#[derive(Debug)]
+pub struct JsonPathError {
+    message: String,
+}
+
+impl JsonPathError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for JsonPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid JSONPath expression: {}", self.message)
+    }
+}
+
+impl Error for JsonPathError {}
+
+/// Selects all nodes in `json` matching the JSONPath expression `path`.
+pub fn select<'a>(json: &'a Json, path: &str) -> Result<Vec<&'a Value>, JsonPathError> {
+    let segments = parse_path(path)?;
+
+    let mut results = vec![];
+    for root in json.values() {
+        results.extend(apply_segments(&segments, root));
+    }
+    Ok(results)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Dollar,
+    Dot,
+    DotDot,
+    Star,
+    LBracket,
+    RBracket,
+    LParen,
+    RParen,
+    Colon,
+    Comma,
+    Question,
+    At,
+    Ident(String),
+    String(String),
+    Number(f64),
+    Op(CompareOp),
+    AndAnd,
+    OrOr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+fn tokenize(path: &str) -> Result<Vec<Token>, JsonPathError> {
+    let chars = path.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ' ' | '\t' | '\n' | '\r' => i += 1,
+            '$' => {
+                tokens.push(Token::Dollar);
+                i += 1;
+            }
+            '@' => {
+                tokens.push(Token::At);
+                i += 1;
+            }
+            '*' => {
+                tokens.push(Token::Star);
+                i += 1;
+            }
+            '[' => {
+                tokens.push(Token::LBracket);
+                i += 1;
+            }
+            ']' => {
+                tokens.push(Token::RBracket);
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ':' => {
+                tokens.push(Token::Colon);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '?' => {
+                tokens.push(Token::Question);
+                i += 1;
+            }
+            '.' => {
+                if chars.get(i + 1) == Some(&'.') {
+                    tokens.push(Token::DotDot);
+                    i += 2;
+                } else {
+                    tokens.push(Token::Dot);
+                    i += 1;
+                }
+            }
+            '\'' | '"' => {
+                let quote = c;
+                let mut s = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        Some(&ch) if ch == quote => {
+                            i += 1;
+                            break;
+                        }
+                        Some(&ch) => {
+                            s.push(ch);
+                            i += 1;
+                        }
+                        None => return Err(JsonPathError::new("unterminated string literal")),
+                    }
+                }
+                tokens.push(Token::String(s));
+            }
+            '=' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Op(CompareOp::Eq));
+                i += 2;
+            }
+            '!' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Op(CompareOp::Ne));
+                i += 2;
+            }
+            '<' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Op(CompareOp::Le));
+                i += 2;
+            }
+            '>' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Op(CompareOp::Ge));
+                i += 2;
+            }
+            '<' => {
+                tokens.push(Token::Op(CompareOp::Lt));
+                i += 1;
+            }
+            '>' => {
+                tokens.push(Token::Op(CompareOp::Gt));
+                i += 1;
+            }
+            '&' if chars.get(i + 1) == Some(&'&') => {
+                tokens.push(Token::AndAnd);
+                i += 2;
+            }
+            '|' if chars.get(i + 1) == Some(&'|') => {
+                tokens.push(Token::OrOr);
+                i += 2;
+            }
+            '-' | '0'..='9' => {
+                let start = i;
+                i += 1;
+                while matches!(chars.get(i), Some(c) if c.is_ascii_digit() || *c == '.') {
+                    i += 1;
+                }
+                let slice = chars[start..i].iter().collect::<String>();
+                let n = slice
+                    .parse::<f64>()
+                    .map_err(|_| JsonPathError::new(format!("bad number literal `{}`", slice)))?;
+                tokens.push(Token::Number(n));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                i += 1;
+                while matches!(chars.get(i), Some(c) if c.is_alphanumeric() || *c == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            c => return Err(JsonPathError::new(format!("unexpected character `{}`", c))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone)]
+enum UnionItem {
+    Key(String),
+    Index(i64),
+}
+
+#[derive(Debug, Clone)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Union(Vec<UnionItem>),
+    Filter(FilterExpr),
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Root,
+    Select(Selector),
+    Descendant(Selector),
+}
+
+#[derive(Debug, Clone)]
+enum FilterExpr {
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Compare(Operand, CompareOp, Operand),
+    Exists(Operand),
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    Current(Vec<String>),
+    Literal(Literal),
+}
+
+#[derive(Debug, Clone)]
+enum Literal {
+    Number(f64),
+    String(String),
+    Bool(bool),
+    Null,
+}
+
+struct PathParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl PathParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn expect(&mut self, token: &Token) -> Result<(), JsonPathError> {
+        match self.next() {
+            Some(ref t) if t == token => Ok(()),
+            other => Err(JsonPathError::new(format!(
+                "expected {:?}, found {:?}",
+                token, other
+            ))),
+        }
+    }
+
+    fn parse_segments(&mut self) -> Result<Vec<Segment>, JsonPathError> {
+        self.expect(&Token::Dollar)?;
+        let mut segments = vec![Segment::Root];
+
+        while self.peek().is_some() {
+            match self.next().unwrap() {
+                Token::Dot => match self.next() {
+                    Some(Token::Star) => segments.push(Segment::Select(Selector::Wildcard)),
+                    Some(Token::Ident(name)) => {
+                        segments.push(Segment::Select(Selector::Name(name)))
+                    }
+                    other => {
+                        return Err(JsonPathError::new(format!(
+                            "expected a name or `*` after `.`, found {:?}",
+                            other
+                        )))
+                    }
+                },
+                Token::DotDot => {
+                    let sel = match self.next() {
+                        Some(Token::Star) => Selector::Wildcard,
+                        Some(Token::Ident(name)) => Selector::Name(name),
+                        Some(Token::LBracket) => {
+                            let sel = self.parse_bracket()?;
+                            self.expect(&Token::RBracket)?;
+                            sel
+                        }
+                        other => {
+                            return Err(JsonPathError::new(format!(
+                                "expected a selector after `..`, found {:?}",
+                                other
+                            )))
+                        }
+                    };
+                    segments.push(Segment::Descendant(sel));
+                }
+                Token::LBracket => {
+                    let sel = self.parse_bracket()?;
+                    self.expect(&Token::RBracket)?;
+                    segments.push(Segment::Select(sel));
+                }
+                other => {
+                    return Err(JsonPathError::new(format!("unexpected token {:?}", other)))
+                }
+            }
+        }
+
+        Ok(segments)
+    }
+
+    fn parse_bracket(&mut self) -> Result<Selector, JsonPathError> {
+        match self.peek() {
+            Some(Token::Star) => {
+                self.next();
+                Ok(Selector::Wildcard)
+            }
+            Some(Token::Question) => {
+                self.next();
+                self.expect(&Token::LParen)?;
+                let expr = self.parse_or()?;
+                self.expect(&Token::RParen)?;
+                Ok(Selector::Filter(expr))
+            }
+            Some(Token::String(_)) => {
+                let mut keys = vec![];
+                loop {
+                    match self.next() {
+                        Some(Token::String(s)) => keys.push(UnionItem::Key(s)),
+                        other => {
+                            return Err(JsonPathError::new(format!(
+                                "expected a quoted key, found {:?}",
+                                other
+                            )))
+                        }
+                    }
+                    if matches!(self.peek(), Some(Token::Comma)) {
+                        self.next();
+                    } else {
+                        break;
+                    }
+                }
+                if keys.len() == 1 {
+                    let UnionItem::Key(k) = keys.remove(0) else {
+                        unreachable!()
+                    };
+                    Ok(Selector::Name(k))
+                } else {
+                    Ok(Selector::Union(keys))
+                }
+            }
+            Some(Token::Number(_)) => self.parse_index_slice_or_union(),
+            Some(Token::Colon) => self.parse_slice_from_colon(),
+            other => Err(JsonPathError::new(format!(
+                "expected an index, slice, key, `*`, or filter inside `[...]`, found {:?}",
+                other
+            ))),
+        }
+    }
+
+    fn parse_signed_int(&mut self) -> Result<i64, JsonPathError> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(n as i64),
+            other => Err(JsonPathError::new(format!(
+                "expected an integer, found {:?}",
+                other
+            ))),
+        }
+    }
+
+    fn parse_slice_from_colon(&mut self) -> Result<Selector, JsonPathError> {
+        self.expect(&Token::Colon)?;
+
+        let end = if matches!(self.peek(), Some(Token::Colon) | Some(Token::RBracket)) {
+            None
+        } else {
+            Some(self.parse_signed_int()?)
+        };
+
+        let step = if matches!(self.peek(), Some(Token::Colon)) {
+            self.next();
+            Some(self.parse_signed_int()?)
+        } else {
+            None
+        };
+
+        Ok(Selector::Slice(None, end, step))
+    }
+
+    fn parse_index_slice_or_union(&mut self) -> Result<Selector, JsonPathError> {
+        let first = self.parse_signed_int()?;
+
+        if matches!(self.peek(), Some(Token::Colon)) {
+            self.next();
+            let end = if matches!(self.peek(), Some(Token::Colon) | Some(Token::RBracket)) {
+                None
+            } else {
+                Some(self.parse_signed_int()?)
+            };
+
+            let step = if matches!(self.peek(), Some(Token::Colon)) {
+                self.next();
+                Some(self.parse_signed_int()?)
+            } else {
+                None
+            };
+
+            return Ok(Selector::Slice(Some(first), end, step));
+        }
+
+        if matches!(self.peek(), Some(Token::Comma)) {
+            let mut items = vec![UnionItem::Index(first)];
+            while matches!(self.peek(), Some(Token::Comma)) {
+                self.next();
+                items.push(UnionItem::Index(self.parse_signed_int()?));
+            }
+            return Ok(Selector::Union(items));
+        }
+
+        Ok(Selector::Index(first))
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpr, JsonPathError> {
+        let mut lhs = self.parse_and()?;
+        while matches!(self.peek(), Some(Token::OrOr)) {
+            self.next();
+            let rhs = self.parse_and()?;
+            lhs = FilterExpr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, JsonPathError> {
+        let mut lhs = self.parse_comparison()?;
+        while matches!(self.peek(), Some(Token::AndAnd)) {
+            self.next();
+            let rhs = self.parse_comparison()?;
+            lhs = FilterExpr::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_comparison(&mut self) -> Result<FilterExpr, JsonPathError> {
+        let lhs = self.parse_operand()?;
+
+        if let Some(Token::Op(op)) = self.peek().cloned() {
+            self.next();
+            let rhs = self.parse_operand()?;
+            return Ok(FilterExpr::Compare(lhs, op, rhs));
+        }
+
+        Ok(FilterExpr::Exists(lhs))
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, JsonPathError> {
+        match self.next() {
+            Some(Token::At) => {
+                let mut path = vec![];
+                while matches!(self.peek(), Some(Token::Dot)) {
+                    self.next();
+                    match self.next() {
+                        Some(Token::Ident(name)) => path.push(name),
+                        other => {
+                            return Err(JsonPathError::new(format!(
+                                "expected a field name after `.`, found {:?}",
+                                other
+                            )))
+                        }
+                    }
+                }
+                Ok(Operand::Current(path))
+            }
+            Some(Token::Number(n)) => Ok(Operand::Literal(Literal::Number(n))),
+            Some(Token::String(s)) => Ok(Operand::Literal(Literal::String(s))),
+            Some(Token::Ident(ref s)) if s == "true" => Ok(Operand::Literal(Literal::Bool(true))),
+            Some(Token::Ident(ref s)) if s == "false" => {
+                Ok(Operand::Literal(Literal::Bool(false)))
+            }
+            Some(Token::Ident(ref s)) if s == "null" => Ok(Operand::Literal(Literal::Null)),
+            other => Err(JsonPathError::new(format!(
+                "expected `@`, a literal, found {:?}",
+                other
+            ))),
+        }
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment>, JsonPathError> {
+    let tokens = tokenize(path)?;
+    let mut parser = PathParser { tokens, pos: 0 };
+    parser.parse_segments()
+}
+
+fn apply_segments<'a>(segments: &[Segment], root: &'a Value) -> Vec<&'a Value> {
+    let mut current = vec![root];
+
+    for seg in segments.iter().skip(1) {
+        let mut next = vec![];
+        for node in current {
+            match seg {
+                Segment::Select(sel) => next.extend(apply_selector(sel, node)),
+                Segment::Descendant(sel) => {
+                    for d in collect_descendants(node) {
+                        next.extend(apply_selector(sel, d));
+                    }
+                }
+                Segment::Root => {}
+            }
+        }
+        current = next;
+    }
+
+    current
+}
+
+fn collect_descendants(node: &Value) -> Vec<&Value> {
+    let mut out = vec![node];
+    match node.kind() {
+        ValueKind::Array(a) => {
+            for v in a {
+                out.extend(collect_descendants(v));
+            }
+        }
+        ValueKind::Object(o) => {
+            for v in o.values() {
+                out.extend(collect_descendants(v));
+            }
+        }
+        _ => {}
+    }
+    out
+}
+
+fn apply_selector<'a>(sel: &Selector, node: &'a Value) -> Vec<&'a Value> {
+    match sel {
+        Selector::Name(name) => match node.kind() {
+            ValueKind::Object(o) => o.get(name).into_iter().collect(),
+            _ => vec![],
+        },
+        Selector::Wildcard => match node.kind() {
+            ValueKind::Array(a) => a.iter().collect(),
+            ValueKind::Object(o) => o.values().collect(),
+            _ => vec![],
+        },
+        Selector::Index(i) => match node.kind() {
+            ValueKind::Array(a) => resolve_index(a.len(), *i)
+                .and_then(|idx| a.get(idx))
+                .into_iter()
+                .collect(),
+            _ => vec![],
+        },
+        Selector::Slice(start, end, step) => match node.kind() {
+            ValueKind::Array(a) => slice_array(a, *start, *end, *step),
+            _ => vec![],
+        },
+        Selector::Union(items) => match node.kind() {
+            ValueKind::Array(a) => items
+                .iter()
+                .filter_map(|item| match item {
+                    UnionItem::Index(i) => resolve_index(a.len(), *i).and_then(|idx| a.get(idx)),
+                    UnionItem::Key(_) => None,
+                })
+                .collect(),
+            ValueKind::Object(o) => items
+                .iter()
+                .filter_map(|item| match item {
+                    UnionItem::Key(k) => o.get(k),
+                    UnionItem::Index(_) => None,
+                })
+                .collect(),
+            _ => vec![],
+        },
+        Selector::Filter(expr) => match node.kind() {
+            ValueKind::Array(a) => a.iter().filter(|v| eval_filter(expr, v)).collect(),
+            ValueKind::Object(o) => o.values().filter(|v| eval_filter(expr, v)).collect(),
+            _ => vec![],
+        },
+    }
+}
+
+fn resolve_index(len: usize, i: i64) -> Option<usize> {
+    if i >= 0 {
+        let idx = i as usize;
+        (idx < len).then_some(idx)
+    } else {
+        let idx = len as i64 + i;
+        (idx >= 0).then_some(idx as usize)
+    }
+}
+
+fn slice_array(a: &[Value], start: Option<i64>, end: Option<i64>, step: Option<i64>) -> Vec<&Value> {
+    let len = a.len() as i64;
+    let step = step.unwrap_or(1);
+    if step == 0 || len == 0 {
+        return vec![];
+    }
+
+    let clamp = |i: i64| -> i64 {
+        let i = if i < 0 { len + i } else { i };
+        i.clamp(0, len)
+    };
+
+    let (start, end) = if step > 0 {
+        (clamp(start.unwrap_or(0)), clamp(end.unwrap_or(len)))
+    } else {
+        (
+            clamp(start.unwrap_or(len - 1)).min(len - 1),
+            end.map(clamp).unwrap_or(-1),
+        )
+    };
+
+    let mut out = vec![];
+    let mut i = start;
+    if step > 0 {
+        while i < end {
+            out.push(&a[i as usize]);
+            i += step;
+        }
+    } else {
+        while i > end {
+            if i < len {
+                out.push(&a[i as usize]);
+            }
+            i += step;
+        }
+    }
+    out
+}
+
+fn eval_filter(expr: &FilterExpr, node: &Value) -> bool {
+    match expr {
+        FilterExpr::Or(l, r) => eval_filter(l, node) || eval_filter(r, node),
+        FilterExpr::And(l, r) => eval_filter(l, node) && eval_filter(r, node),
+        FilterExpr::Exists(op) => resolve_operand(op, node).is_some(),
+        FilterExpr::Compare(l, cmp, r) => {
+            match (resolve_operand(l, node), resolve_operand(r, node)) {
+                (Some(lv), Some(rv)) => compare_literals(&lv, *cmp, &rv),
+                _ => false,
+            }
+        }
+    }
+}
+
+fn resolve_operand(op: &Operand, node: &Value) -> Option<Literal> {
+    match op {
+        Operand::Literal(l) => Some(l.clone()),
+        Operand::Current(path) => {
+            let mut cur = node;
+            for key in path {
+                let ValueKind::Object(o) = cur.kind() else {
+                    return None;
+                };
+                cur = o.get(key)?;
+            }
+            literal_of(cur)
+        }
+    }
+}
+
+fn literal_of(v: &Value) -> Option<Literal> {
+    match v.kind() {
+        ValueKind::Null => Some(Literal::Null),
+        ValueKind::Bool(b) => Some(Literal::Bool(*b)),
+        ValueKind::String(s) => Some(Literal::String(s.clone())),
+        ValueKind::Number(n) => Some(Literal::Number(number_as_f64(n))),
+        ValueKind::Array(_) | ValueKind::Object(_) => None,
+    }
+}
+
+fn number_as_f64(n: &Number) -> f64 {
+    match n {
+        Number::Integer(i) => *i as f64,
+        Number::Float(f) => *f,
+    }
+}
+
+fn compare_literals(l: &Literal, op: CompareOp, r: &Literal) -> bool {
+    match (l, r) {
+        (Literal::Number(a), Literal::Number(b)) => match op {
+            CompareOp::Eq => a == b,
+            CompareOp::Ne => a != b,
+            CompareOp::Lt => a < b,
+            CompareOp::Le => a <= b,
+            CompareOp::Gt => a > b,
+            CompareOp::Ge => a >= b,
+        },
+        (Literal::String(a), Literal::String(b)) => match op {
+            CompareOp::Eq => a == b,
+            CompareOp::Ne => a != b,
+            CompareOp::Lt => a < b,
+            CompareOp::Le => a <= b,
+            CompareOp::Gt => a > b,
+            CompareOp::Ge => a >= b,
+        },
+        (Literal::Bool(a), Literal::Bool(b)) => match op {
+            CompareOp::Eq => a == b,
+            CompareOp::Ne => a != b,
+            _ => false,
+        },
+        (Literal::Null, Literal::Null) => matches!(op, CompareOp::Eq),
+        _ => matches!(op, CompareOp::Ne),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(json: &Json, path: &str) -> Vec<String> {
+        select(json, path)
+            .unwrap()
+            .into_iter()
+            .map(crate::encode::to_json_str_value)
+            .collect()
+    }
+
+    #[test]
+    fn test_child_access() {
+        let json = crate::from_json_str(r#"{"store":{"name":"acme"}}"#).unwrap();
+        assert_eq!(values(&json, "$.store.name"), vec!["\"acme\""]);
+        assert_eq!(values(&json, "$['store']['name']"), vec!["\"acme\""]);
+    }
+
+    #[test]
+    fn test_wildcard_and_index() {
+        let json = crate::from_json_str(r#"{"a":[1,2,3]}"#).unwrap();
+        assert_eq!(values(&json, "$.a[*]"), vec!["1", "2", "3"]);
+        assert_eq!(values(&json, "$.a[-1]"), vec!["3"]);
+        assert_eq!(values(&json, "$.a[0,2]"), vec!["1", "3"]);
+    }
+
+    #[test]
+    fn test_slice() {
+        let json = crate::from_json_str(r#"[0,1,2,3,4]"#).unwrap();
+        assert_eq!(values(&json, "$[1:3]"), vec!["1", "2"]);
+        assert_eq!(values(&json, "$[:2]"), vec!["0", "1"]);
+    }
+
+    #[test]
+    fn test_recursive_descent() {
+        let json =
+            crate::from_json_str(r#"{"store":{"book":[{"title":"a"},{"title":"b"}]}}"#).unwrap();
+        assert_eq!(values(&json, "$..title"), vec!["\"a\"", "\"b\""]);
+    }
+
+    #[test]
+    fn test_filter() {
+        let json = crate::from_json_str(
+            r#"{"store":{"book":[{"price":8,"title":"a"},{"price":22,"title":"b"}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            values(&json, "$.store.book[?(@.price < 10)].title"),
+            vec!["\"a\""]
+        );
+        assert_eq!(
+            values(
+                &json,
+                "$.store.book[?(@.price > 5 && @.price < 100)].title"
+            ),
+            vec!["\"a\"", "\"b\""]
+        );
+    }
+}
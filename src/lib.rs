@@ -1,20 +1,34 @@
+mod cursor;
+mod encode;
+mod jsonpath;
 mod parser;
 
-use parser::ValueKind;
+pub use cursor::JsonCursor;
+pub use jsonpath::{select, JsonPathError};
+pub use parser::{Location, Number, Object, Value, ValueKind};
 
-use self::parser::Value;
 use std::{error::Error, fmt};
 
 #[derive(Debug)]
 pub struct Json(Vec<Value>);
 
 impl Json {
+    fn values(&self) -> &[Value] {
+        &self.0
+    }
+
     pub fn dump(&self) {
         for v in &self.0 {
             Self::dump_inner(None, v, 0);
         }
     }
 
+    /// Serializes this value back to a JSON string, pretty-printed with
+    /// `indent` spaces per nesting level.
+    pub fn to_string_pretty(&self, indent: usize) -> String {
+        encode::to_json_str_pretty(self, indent)
+    }
+
     fn dump_inner(key: Option<&str>, value: &Value, level: usize) {
         for _ in 0..level {
             print!("  ");
@@ -49,31 +63,68 @@ impl Json {
             ValueKind::Null => "null".into(),
             ValueKind::Object(_) => "Object".into(),
             ValueKind::Array(_) => "Array".into(),
-            ValueKind::String(s) => s.to_owned(),
+            ValueKind::String(s) => encode::escape_json_string(s),
             ValueKind::Number(n) => n.to_string(),
         }
     }
 }
 
+/// Serializes this value back to a compact JSON string.
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", encode::to_json_str(self))
+    }
+}
+
 #[derive(Debug)]
 pub struct ParseError {
     pos: usize,
     kind: ParseErrorKind,
+    /// Context frames, outermost first, e.g. "while parsing object value for key `foo`".
+    context: Vec<String>,
+    source: Option<SourceSpan>,
+}
+
+#[derive(Debug)]
+struct SourceSpan {
+    line: usize,
+    column: usize,
+    line_text: String,
 }
 
 #[derive(Debug)]
 pub enum ParseErrorKind {
     Syntax,
     Number,
+    DuplicateKey,
 }
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "Parse Error occurred at position {}, reason: {:?}",
-            self.pos, self.kind
-        )
+        match &self.source {
+            Some(s) => {
+                writeln!(
+                    f,
+                    "Parse Error at line {}, column {}, reason: {:?}",
+                    s.line, s.column, self.kind
+                )?;
+                writeln!(f, "{}", s.line_text)?;
+                writeln!(f, "{}^", " ".repeat(s.column.saturating_sub(1)))?;
+            }
+            None => {
+                writeln!(
+                    f,
+                    "Parse Error occurred at position {}, reason: {:?}",
+                    self.pos, self.kind
+                )?;
+            }
+        }
+
+        for ctx in self.context.iter().rev() {
+            writeln!(f, "  {}", ctx)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -84,6 +135,8 @@ impl ParseError {
         Self {
             pos,
             kind: ParseErrorKind::Syntax,
+            context: vec![],
+            source: None,
         }
     }
 
@@ -91,8 +144,65 @@ impl ParseError {
         Self {
             pos,
             kind: ParseErrorKind::Number,
+            context: vec![],
+            source: None,
+        }
+    }
+
+    pub fn duplicate_key(pos: usize) -> Self {
+        Self {
+            pos,
+            kind: ParseErrorKind::DuplicateKey,
+            context: vec![],
+            source: None,
         }
     }
+
+    /// The 0-based byte offset the error occurred at.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// The context trail active when the error occurred, outermost first.
+    pub fn context(&self) -> &[String] {
+        &self.context
+    }
+
+    pub(crate) fn with_context(mut self, context: Vec<String>) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Attaches line/column information derived from `src`, the full source
+    /// text the error's position was measured against.
+    pub(crate) fn with_source(mut self, src: &[u8]) -> Self {
+        self.source = Some(locate(src, self.pos));
+        self
+    }
+}
+
+/// Translates a byte offset into a 1-based (line, column) plus the text of
+/// that line, by scanning newlines in `src`.
+fn locate(src: &[u8], pos: usize) -> SourceSpan {
+    let pos = pos.min(src.len());
+
+    let line_start = src[..pos].iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    let line_end = src[pos..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(src.len(), |i| pos + i);
+
+    let line = src[..pos].iter().filter(|&&b| b == b'\n').count() + 1;
+    // `column` must be a character offset, not a byte offset, so that the
+    // caret in `Display for ParseError` lines up under multi-byte UTF-8.
+    let column = String::from_utf8_lossy(&src[line_start..pos]).chars().count() + 1;
+    let line_text = String::from_utf8_lossy(&src[line_start..line_end]).into_owned();
+
+    SourceSpan {
+        line,
+        column,
+        line_text,
+    }
 }
 
 pub fn from_json_str(json_str: &str) -> Result<Json, ParseError> {
@@ -100,6 +210,16 @@ pub fn from_json_str(json_str: &str) -> Result<Json, ParseError> {
     Ok(res)
 }
 
+/// Serializes `json` back to a compact JSON string, one top-level value per line.
+pub fn to_json_str(json: &Json) -> String {
+    encode::to_json_str(json)
+}
+
+/// Serializes `json` back to a JSON string, pretty-printed with `indent` spaces per level.
+pub fn to_json_str_pretty(json: &Json, indent: usize) -> String {
+    encode::to_json_str_pretty(json, indent)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,4 +244,58 @@ mod tests {
 
         res.unwrap().dump();
     }
+
+    #[test]
+    fn test_error_context_and_display() {
+        let json = "{\n  \"a\": [1, 2, nope]\n}";
+        let err = from_json_str(json).unwrap_err();
+
+        assert!(err.context().iter().any(|c| c == "in array element 2"));
+        assert!(err
+            .context()
+            .iter()
+            .any(|c| c == "while parsing object value for key `a`"));
+
+        let rendered = err.to_string();
+        assert!(rendered.contains("line 2"));
+        assert!(rendered.contains("nope"));
+        assert!(rendered.contains('^'));
+    }
+
+    #[test]
+    fn test_error_column_counts_chars_not_bytes() {
+        let json = "{\"a\": \"héllo\", \"b\": nope}";
+        let err = from_json_str(json).unwrap_err();
+
+        // `é` is 2 bytes but 1 char; the column/caret must line up with the
+        // `n` of `nope` in character terms, not byte terms.
+        let rendered = err.to_string();
+        let caret_line = rendered.lines().nth(2).unwrap();
+        assert_eq!(caret_line.len() - 1, 20);
+        assert!(rendered.contains("column 21"));
+    }
+
+    #[test]
+    fn test_json_to_string_round_trips() {
+        let json = from_json_str(r#"{"a":1,"b":"hi"}"#).unwrap();
+
+        let compact = json.to_string();
+        assert!(from_json_str(&compact).is_ok());
+
+        let pretty = json.to_string_pretty(2);
+        assert!(pretty.contains('\n'));
+        assert!(from_json_str(&pretty).is_ok());
+    }
+
+    #[test]
+    fn test_dump_escapes_strings() {
+        let json = from_json_str(r#"["line\tbreak"]"#).unwrap();
+        // format_value quotes/escapes strings instead of printing them raw.
+        json.dump();
+
+        let ValueKind::Array(a) = json.values()[0].kind() else {
+            panic!("expected an array value");
+        };
+        assert_eq!(Json::format_value(&a[0]), r#""line\tbreak""#);
+    }
 }
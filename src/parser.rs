@@ -2,12 +2,28 @@ use crate::{Json, ParseError};
 use std::collections::{HashMap, HashSet};
 
 const JSON_QUOTE: u8 = b'"';
-const JSON_NUMBER_CHARS: &str = "0123456789-e.";
+const JSON_NUMBER_CHARS: &str = "0123456789-+.eE";
 const JSON_WHITESPACE_CHARS: &str = " \n\r\t";
 const LEN_TRUE: usize = 4;
 const LEN_FALSE: usize = 5;
 const LEN_NULL: usize = 4;
 
+/// Returns the byte width of the UTF-8 scalar whose first byte is `b`, or `0`
+/// if `b` can't start a scalar (a continuation or invalid leading byte).
+fn utf8_char_width(b: u8) -> usize {
+    if b & 0x80 == 0x00 {
+        1
+    } else if b & 0xE0 == 0xC0 {
+        2
+    } else if b & 0xF0 == 0xE0 {
+        3
+    } else if b & 0xF8 == 0xF0 {
+        4
+    } else {
+        0
+    }
+}
+
 #[derive(Debug)]
 pub struct Location(usize, usize);
 
@@ -44,6 +60,10 @@ impl Value {
         &self.value
     }
 
+    pub(crate) fn into_kind(self) -> ValueKind {
+        self.value
+    }
+
     fn null(loc: Location) -> Value {
         Self::new(ValueKind::Null, loc)
     }
@@ -68,11 +88,76 @@ impl Value {
         Self::new(ValueKind::Array(value), loc)
     }
 
-    fn object(value: HashMap<String, Value>, loc: Location) -> Value {
+    fn object(value: Object, loc: Location) -> Value {
         Self::new(ValueKind::Object(value), loc)
     }
 }
 
+/// A JSON object that preserves insertion (document) order while still
+/// offering `HashMap`-like lookup by key.
+#[derive(Debug, Default)]
+pub struct Object {
+    entries: Vec<(String, Value)>,
+    index: HashMap<String, usize>,
+}
+
+impl Object {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.index.contains_key(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    /// Inserts `key`/`value` in document order. Returns `false` without
+    /// modifying the object if `key` is already present.
+    fn insert(&mut self, key: String, value: Value) -> bool {
+        if self.index.contains_key(&key) {
+            return false;
+        }
+        self.index.insert(key.clone(), self.entries.len());
+        self.entries.push((key, value));
+        true
+    }
+}
+
+impl<'a> IntoIterator for &'a Object {
+    type Item = (&'a String, &'a Value);
+    type IntoIter = std::iter::Map<
+        std::slice::Iter<'a, (String, Value)>,
+        fn(&'a (String, Value)) -> (&'a String, &'a Value),
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Number {
     Integer(i64),
@@ -90,7 +175,7 @@ impl ToString for Number {
 
 #[derive(Debug)]
 pub enum ValueKind {
-    Object(HashMap<String, Value>),
+    Object(Object),
     Array(Vec<Value>),
     String(String),
     Number(Number),
@@ -104,6 +189,16 @@ pub fn parse_str(s: &str) -> Result<Json, ParseError> {
     parser.parse()
 }
 
+/// Parses a single value starting at `pos` within `bytes`, returning the value
+/// and the byte offset just past it. Lets callers (e.g. [`crate::cursor`])
+/// parse one node out of a larger buffer without building the whole tree.
+pub(crate) fn parse_value_at(bytes: &[u8], pos: usize) -> Result<(Value, usize), ParseError> {
+    let mut parser = Parser::new(bytes);
+    parser.pos = pos;
+    let value = parser.parse_bytes()?;
+    Ok((value, parser.pos))
+}
+
 #[derive(Debug)]
 pub struct Parser<'a> {
     pos: usize,
@@ -111,6 +206,7 @@ pub struct Parser<'a> {
     values: Vec<Value>,
     number_chars: HashSet<u8>,
     whitespace: HashSet<u8>,
+    context: Vec<String>,
 }
 
 impl<'a> Parser<'a> {
@@ -121,9 +217,40 @@ impl<'a> Parser<'a> {
             values: vec![],
             number_chars: JSON_NUMBER_CHARS.chars().map(|x| x as u8).collect(),
             whitespace: JSON_WHITESPACE_CHARS.chars().map(|x| x as u8).collect(),
+            context: vec![],
         }
     }
 
+    /// Builds a syntax error at `pos`, enriched with the current context
+    /// trail and the source line/column it occurred at.
+    fn err_syntax(&self, pos: usize) -> ParseError {
+        ParseError::syntax(pos)
+            .with_context(self.context.clone())
+            .with_source(self.bytes)
+    }
+
+    /// Builds a number error at `pos`, enriched like [`Parser::err_syntax`].
+    fn err_number(&self, pos: usize) -> ParseError {
+        ParseError::number(pos)
+            .with_context(self.context.clone())
+            .with_source(self.bytes)
+    }
+
+    /// Builds a duplicate-key error at `pos`, enriched like [`Parser::err_syntax`].
+    fn err_duplicate_key(&self, pos: usize) -> ParseError {
+        ParseError::duplicate_key(pos)
+            .with_context(self.context.clone())
+            .with_source(self.bytes)
+    }
+
+    fn push_context(&mut self, ctx: String) {
+        self.context.push(ctx);
+    }
+
+    fn pop_context(&mut self) {
+        self.context.pop();
+    }
+
     pub fn parse(mut self) -> Result<Json, ParseError> {
         while self.pos < self.bytes.len() {
             if self.skip_whitespace().is_err() {
@@ -147,7 +274,7 @@ impl<'a> Parser<'a> {
             b'-' | b'0'..=b'9' => self.parse_number()?,
             b't' | b'f' => self.parse_bool()?,
             b'n' => self.parse_null()?,
-            _ => return Err(ParseError::syntax(self.pos)),
+            _ => return Err(self.err_syntax(self.pos)),
         };
 
         Ok(value)
@@ -159,7 +286,7 @@ impl<'a> Parser<'a> {
             self.pos += LEN_NULL;
             return Ok(Value::null(Location(i, i + LEN_NULL)));
         }
-        Err(ParseError::syntax(self.pos))
+        Err(self.err_syntax(self.pos))
     }
 
     fn parse_bool(&mut self) -> Result<Value, ParseError> {
@@ -171,7 +298,7 @@ impl<'a> Parser<'a> {
             self.pos += LEN_FALSE;
             return Ok(Value::bool(false, Location(i, i + LEN_FALSE)));
         }
-        Err(ParseError::syntax(self.pos))
+        Err(self.err_syntax(self.pos))
     }
 
     fn parse_number(&mut self) -> Result<Value, ParseError> {
@@ -192,38 +319,122 @@ impl<'a> Parser<'a> {
             self.pos += cursor;
             return Ok(Value::number_int(i, loc));
         } else if let Ok(f) = num_slice.parse::<f64>() {
+            // JSON has no representation for non-finite numbers; reject here
+            // rather than let `inf`/`-inf`/`nan` leak out as unparseable output.
+            if !f.is_finite() {
+                return Err(self.err_number(self.pos));
+            }
             self.pos += cursor;
             return Ok(Value::number_float(f, loc));
         }
-        Err(ParseError::number(self.pos))
+        Err(self.err_number(self.pos))
     }
 
     fn parse_string(&mut self) -> Result<Value, ParseError> {
         let start_pos = self.pos;
-        let mut closed = false;
         self.pos += 1; // skip first '"'
 
-        let mut cursor = 0;
-        while self.pos + cursor < self.bytes.len() {
-            if self.bytes[self.pos + cursor] == JSON_QUOTE {
-                // FIXME: when escaped
-                closed = true;
-                break;
+        let mut s = String::new();
+        loop {
+            if self.pos >= self.bytes.len() {
+                return Err(self.err_syntax(self.pos));
+            }
+
+            let c = self.decode_char_at(self.pos)?;
+
+            match c {
+                c if c as u8 == JSON_QUOTE => {
+                    self.pos += 1;
+                    break;
+                }
+                '\\' => {
+                    self.pos += 1;
+                    s.push(self.parse_escape()?);
+                }
+                c => {
+                    s.push(c);
+                    self.pos += c.len_utf8();
+                }
             }
-            cursor += 1;
         }
 
-        if !closed {
-            return Err(ParseError::syntax(self.pos));
+        Ok(Value::string(s, Location(start_pos, self.pos)))
+    }
+
+    /// Decodes the single UTF-8 scalar starting at `pos`, validating only the
+    /// bytes that scalar needs rather than the whole remainder of `self.bytes`.
+    fn decode_char_at(&self, pos: usize) -> Result<char, ParseError> {
+        let width = utf8_char_width(self.bytes[pos]);
+        if width == 0 || pos + width > self.bytes.len() {
+            return Err(self.err_syntax(pos));
         }
 
-        let s = self.bytes[self.pos..self.pos + cursor]
-            .iter()
-            .map(|x| *x as char)
-            .collect::<String>();
-        self.pos += cursor + 1; //skip closing '"'
+        std::str::from_utf8(&self.bytes[pos..pos + width])
+            .map_err(|_| self.err_syntax(pos))?
+            .chars()
+            .next()
+            .ok_or_else(|| self.err_syntax(pos))
+    }
 
-        Ok(Value::string(s, Location(start_pos, self.pos)))
+    fn parse_escape(&mut self) -> Result<char, ParseError> {
+        if self.pos >= self.bytes.len() {
+            return Err(self.err_syntax(self.pos));
+        }
+
+        let b = self.bytes[self.pos];
+        self.pos += 1;
+
+        let c = match b {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{08}',
+            b'f' => '\u{0C}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => return self.parse_unicode_escape(),
+            _ => return Err(self.err_syntax(self.pos - 1)),
+        };
+
+        Ok(c)
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, ParseError> {
+        let high = self.parse_hex4()?;
+
+        if (0xD800..=0xDBFF).contains(&high) {
+            if self.bytes.get(self.pos) != Some(&b'\\') || self.bytes.get(self.pos + 1) != Some(&b'u')
+            {
+                return Err(self.err_syntax(self.pos));
+            }
+            self.pos += 2;
+
+            let low = self.parse_hex4()?;
+            if !(0xDC00..=0xDFFF).contains(&low) {
+                return Err(self.err_syntax(self.pos));
+            }
+
+            let scalar = 0x10000 + (high - 0xD800) * 0x400 + (low - 0xDC00);
+            char::from_u32(scalar).ok_or_else(|| self.err_syntax(self.pos))
+        } else if (0xDC00..=0xDFFF).contains(&high) {
+            Err(self.err_syntax(self.pos))
+        } else {
+            char::from_u32(high).ok_or_else(|| self.err_syntax(self.pos))
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, ParseError> {
+        if self.pos + 4 > self.bytes.len() {
+            return Err(self.err_syntax(self.pos));
+        }
+
+        let hex = std::str::from_utf8(&self.bytes[self.pos..self.pos + 4])
+            .map_err(|_| self.err_syntax(self.pos))?;
+        let value = u32::from_str_radix(hex, 16).map_err(|_| self.err_syntax(self.pos))?;
+        self.pos += 4;
+
+        Ok(value)
     }
 
     fn parse_array(&mut self) -> Result<Value, ParseError> {
@@ -231,6 +442,7 @@ impl<'a> Parser<'a> {
         self.pos += 1; // skip first '['
 
         let mut array = vec![];
+        let mut index = 0;
 
         self.skip_whitespace()?;
 
@@ -240,8 +452,11 @@ impl<'a> Parser<'a> {
         }
 
         loop {
-            let val = self.parse_bytes()?;
-            array.push(val);
+            self.push_context(format!("in array element {}", index));
+            let val = self.parse_bytes();
+            self.pop_context();
+            array.push(val?);
+            index += 1;
 
             self.skip_whitespace()?;
 
@@ -254,7 +469,7 @@ impl<'a> Parser<'a> {
                     self.pos += 1;
                     self.skip_whitespace()?;
                 }
-                _ => return Err(ParseError::syntax(self.pos)),
+                _ => return Err(self.err_syntax(self.pos)),
             }
         }
 
@@ -265,32 +480,38 @@ impl<'a> Parser<'a> {
         let start_pos = self.pos;
         self.pos += 1; // skip first '{'
 
-        let mut hm = HashMap::new();
+        let mut object = Object::new();
 
         self.skip_whitespace()?;
 
         if self.bytes[self.pos] == b'}' {
             self.pos += 1; // skip closing ']'
-            return Ok(Value::object(hm, Location(start_pos, self.pos)));
+            return Ok(Value::object(object, Location(start_pos, self.pos)));
         }
 
         loop {
+            let key_pos = self.pos;
             let Value { value: ValueKind::String(key),..} = self.parse_string()? else {
-                return Err(ParseError::syntax(self.pos));
+                return Err(self.err_syntax(self.pos));
             };
 
             self.skip_whitespace()?;
 
             if self.bytes[self.pos] != b':' {
-                return Err(ParseError::syntax(self.pos));
+                return Err(self.err_syntax(self.pos));
             }
             self.pos += 1;
 
             self.skip_whitespace()?;
 
-            let val = self.parse_bytes()?;
+            self.push_context(format!("while parsing object value for key `{}`", key));
+            let val = self.parse_bytes();
+            self.pop_context();
+            let val = val?;
 
-            hm.insert(key, val);
+            if !object.insert(key, val) {
+                return Err(self.err_duplicate_key(key_pos));
+            }
 
             self.skip_whitespace()?;
 
@@ -303,11 +524,11 @@ impl<'a> Parser<'a> {
                     self.pos += 1;
                     self.skip_whitespace()?;
                 }
-                _ => return Err(ParseError::syntax(self.pos)),
+                _ => return Err(self.err_syntax(self.pos)),
             }
         }
 
-        Ok(Value::object(hm, Location(start_pos, self.pos)))
+        Ok(Value::object(object, Location(start_pos, self.pos)))
     }
 
     fn match_number_token(&self, c: &u8) -> bool {
@@ -323,7 +544,7 @@ impl<'a> Parser<'a> {
             }
         }
 
-        Err(ParseError::syntax(self.pos))
+        Err(self.err_syntax(self.pos))
     }
 }
 
@@ -356,6 +577,10 @@ mod tests {
 
         p(r#""""#);
         p(r#""aaa""#);
+        p(r#""\"\\\/\b\f\n\r\t""#);
+        p(r#""é""#);
+        p(r#""😀""#);
+        p(&format!(r#""{}""#, "a".repeat(20_000)));
 
         p(r#"[]"#);
         p(r#"[ ]"#);
@@ -403,5 +628,63 @@ mod tests {
         e(r#"{"a": 1 ,}"#);
         e(r#"{"a": 1, "b": , }"#);
         e(r#"{"a": 1, "b": 2"#);
+
+        // string escapes
+        e(r#""\q""#);
+        e(r#""\u00""#);
+        e(r#""\uD800""#);
+        e(r#""\uD800A""#);
+        e(r#""\uDC00""#);
+        e(r#""\"#);
+
+        // duplicate object keys
+        e(r#"{"a": 1, "a": 2}"#);
+
+        // numbers that overflow f64 to a non-finite value
+        e(r#"1e400"#);
+        e(r#"-1e400"#);
+    }
+
+    fn string_value(s: &str) -> String {
+        let Value {
+            value: ValueKind::String(s),
+            ..
+        } = parse_str(s).unwrap().0.into_iter().next().unwrap()
+        else {
+            panic!("expected a string value");
+        };
+        s
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        assert_eq!(string_value(r#""\"\\\/\b\f\n\r\t""#), "\"\\/\u{08}\u{0C}\n\r\t");
+        assert_eq!(string_value(r#""é""#), "é");
+        assert_eq!(string_value(r#""😀""#), "😀");
+        assert_eq!(string_value(r#""café""#), "café");
+    }
+
+    #[test]
+    fn test_object_preserves_key_order() {
+        let Value {
+            value: ValueKind::Object(o),
+            ..
+        } = parse_str(r#"{"z": 1, "a": 2, "m": 3}"#)
+            .unwrap()
+            .0
+            .into_iter()
+            .next()
+            .unwrap()
+        else {
+            panic!("expected an object value");
+        };
+
+        assert_eq!(
+            o.keys().collect::<Vec<_>>(),
+            vec!["z", "a", "m"]
+        );
+        assert_eq!(o.len(), 3);
+        assert!(o.contains_key("a"));
+        assert!(!o.contains_key("q"));
     }
 }
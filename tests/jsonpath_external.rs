@@ -0,0 +1,31 @@
+//! Exercises `select()` as an external consumer would: the returned nodes
+//! must be matchable against the crate's public value types, not just
+//! `{:?}`-printed. This guards the `pub use` re-exports in `src/lib.rs`
+//! against a privacy regression that unit tests (exempt from the crate
+//! boundary) wouldn't catch.
+
+use ej::{select, Number, ValueKind};
+
+#[test]
+fn select_results_are_matchable_outside_the_crate() {
+    let json = ej::from_json_str(r#"{"users": [{"name": "a"}, {"name": "b"}]}"#).unwrap();
+
+    let names = select(&json, "$.users[*].name").unwrap();
+    assert_eq!(names.len(), 2);
+
+    let ValueKind::String(name) = names[0].kind() else {
+        panic!("expected a string value");
+    };
+    assert_eq!(name, "a");
+}
+
+#[test]
+fn select_results_expose_numbers() {
+    let json = ej::from_json_str(r#"{"count": 3}"#).unwrap();
+
+    let results = select(&json, "$.count").unwrap();
+    let ValueKind::Number(Number::Integer(n)) = results[0].kind() else {
+        panic!("expected an integer value");
+    };
+    assert_eq!(*n, 3);
+}